@@ -2,13 +2,18 @@ use std::sync::Arc;
 
 use anyhow::Context;
 use axum::{
-    extract::State,
-    http::StatusCode,
+    body::Bytes,
+    extract::{Query, State},
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
-use serde::Deserialize;
+use std::collections::HashMap;
+use serde::{
+    ser::{SerializeMap, Serializer},
+    Deserialize, Serialize,
+};
 use serde_json::{json, Value as JsonValue};
 use wasmtime::{
     component::{Component, InstancePre, Linker, Val},
@@ -16,7 +21,10 @@ use wasmtime::{
 };
 use wit_parser::Document;
 
-use component_rpc::{json_to_val, openapi::build_openapi_doc, val_to_json, TypeExt};
+use component_rpc::{
+    from_value, jsonrpc, json_to_val, openapi::build_openapi_doc, scale, to_values, val_to_json,
+    val_to_json_base64, TypeExt,
+};
 
 const USAGE: &str = "component-rpc-server <path-to-component>";
 
@@ -44,6 +52,7 @@ async fn main() -> anyhow::Result<()> {
 
     let app = Router::new()
         .route("/call", post(call))
+        .route("/rpc", post(rpc))
         .route("/openapi.json", get(openapi))
         .with_state(Arc::new(state));
 
@@ -72,8 +81,24 @@ struct CallRequest {
 
 async fn call(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CallRequest>,
-) -> Result<Json<JsonValue>, AppError> {
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<axum::response::Response, AppError> {
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    // `application/octet-stream` carries SCALE-encoded arguments and returns a
+    // SCALE-encoded response; the method name comes from `?method=`.
+    if content_type.starts_with("application/octet-stream") {
+        let bytes = call_scale(&state, &query, &body)?;
+        return Ok(([(CONTENT_TYPE, "application/octet-stream")], bytes).into_response());
+    }
+
+    let body = std::str::from_utf8(&body).context("request body is not valid UTF-8")?;
+    let req = parse_call_request(content_type, &query, body)?;
     let func_name = req.name;
 
     let mut store = Store::new(&state.engine, ());
@@ -85,6 +110,12 @@ async fn call(
         .func(&func_name)
         .with_context(|| format!("No such export {func_name:?}"))?;
 
+    // `?format=base64`/`attachments` encode `list<u8>` compactly; their
+    // arguments likewise accept base64 strings, so they flow through
+    // `from_value` rather than plain `json_to_val`. The request carries no
+    // attachment buffer, so a placeholder marker in an argument is rejected.
+    let binary = matches!(query.get("format").map(String::as_str), Some("base64" | "attachments"));
+
     let mut args = req.args.into_iter();
 
     let mut arg_vals: Vec<Val> = vec![];
@@ -93,7 +124,11 @@ async fn call(
             .next()
             .with_context(|| format!("Missing argument for parameter {idx}"))?;
 
-        arg_vals.push(json_to_val(param_type, arg_json)?);
+        arg_vals.push(if binary {
+            from_value(param_type, arg_json, &[])?
+        } else {
+            json_to_val(param_type, arg_json)?
+        });
     }
 
     let mut result_vals = func
@@ -109,27 +144,227 @@ async fn call(
 
     func.call(&mut store, &arg_vals, &mut result_vals)?;
 
-    Ok(Json(results_to_json(&result_vals)))
+    // The base64 and attachments formats build a `serde_json::Value`, so their
+    // record fields are sorted rather than kept in WIT order; they trade field
+    // order for compact binary transport. The default inline response below
+    // streams through `OrderedResults` and preserves declaration order.
+    let body = match query.get("format").map(String::as_str) {
+        // `list<u8>` rendered inline as base64 strings.
+        Some("base64") => {
+            let mut response = results_envelope(&result_vals, val_to_json_base64);
+            if is_canonical(&query) {
+                canonicalize(&mut response);
+            }
+            serde_json::to_string(&response)?
+        }
+        // `list<u8>` lifted out of band into a shared, base64-encoded buffer.
+        Some("attachments") => {
+            let (jsons, attachments) = to_values(&result_vals);
+            let encoded: Vec<JsonValue> = attachments
+                .iter()
+                .map(|bytes| base64::encode(bytes).into())
+                .collect();
+            let mut response = json!({
+                "result": envelope_from(&result_vals, jsons),
+                "attachments": encoded,
+            });
+            if is_canonical(&query) {
+                canonicalize(&mut response);
+            }
+            serde_json::to_string(&response)?
+        }
+        // The default response preserves WIT record-field order through
+        // streaming serialization; `?canonical=1` instead sorts keys for
+        // deterministic, content-addressable output.
+        _ if is_canonical(&query) => {
+            let mut response = results_to_json(&result_vals);
+            canonicalize(&mut response);
+            serde_json::to_string(&response)?
+        }
+        _ => serde_json::to_string(&OrderedResults(&result_vals))?,
+    };
+    Ok(([(CONTENT_TYPE, "application/json")], body).into_response())
+}
+
+/// Serializes the result envelope while preserving record-field order. Routing
+/// each [`Val`] through [`ValSerialize`](component_rpc::serde::ValSerialize)
+/// keeps WIT declaration order, which a `serde_json::Value` would lose without
+/// the `preserve_order` feature.
+struct OrderedResults<'a>(&'a [Val]);
+
+/// Adapts a single [`Val`] to [`Serialize`] via its [`ValSerialize`] impl.
+struct OrderedVal<'a>(&'a Val);
+
+impl Serialize for OrderedVal<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        component_rpc::serde::ValSerialize::serialize(self.0, serializer)
+    }
 }
 
-async fn openapi(State(state): State<Arc<AppState>>) -> Result<Json<JsonValue>, AppError> {
-    let openapi = build_openapi_doc(&state.doc)?;
+impl Serialize for OrderedResults<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            [] => serializer.serialize_map(Some(0))?.end(),
+            // A bare `result`/`error` envelope is emitted directly, matching
+            // `results_to_json`.
+            [val @ Val::Result(_)] => OrderedVal(val).serialize(serializer),
+            [val] => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("result", &OrderedVal(val))?;
+                map.end()
+            }
+            vals => {
+                let items: Vec<OrderedVal> = vals.iter().map(OrderedVal).collect();
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("results", &items)?;
+                map.end()
+            }
+        }
+    }
+}
+
+/// Run a call whose arguments arrive as a SCALE byte stream, returning the
+/// SCALE encoding of the results concatenated in order.
+fn call_scale(
+    state: &AppState,
+    query: &HashMap<String, String>,
+    body: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let func_name = query
+        .get("method")
+        .context("octet-stream calls require a ?method= query parameter")?;
+
+    let mut store = Store::new(&state.engine, ());
+    let instance = state.instance_pre.instantiate(&mut store)?;
+
+    let func = instance
+        .exports(&mut store)
+        .root()
+        .func(func_name)
+        .with_context(|| format!("No such export {func_name:?}"))?;
+
+    let param_types = func.params(&store);
+    let mut decoder = scale::Decoder::new(body);
+    let mut arg_vals = Vec::with_capacity(param_types.len());
+    for param_type in param_types.iter() {
+        arg_vals.push(decoder.decode(param_type)?);
+    }
+    decoder.finish()?;
+
+    let mut result_vals = func
+        .results(&store)
+        .into_vec()
+        .into_iter()
+        .map(|ty| ty.default_val())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let func = instance
+        .get_func(&mut store, func_name)
+        .context("Instance missing function")?;
+    func.call(&mut store, &arg_vals, &mut result_vals)?;
+
+    let mut out = Vec::new();
+    for val in &result_vals {
+        out.extend(scale::val_to_scale(val)?);
+    }
+    Ok(out)
+}
+
+/// Parse a `/call` body as JSON, or as Hjson when `Content-Type:
+/// application/hjson` or `?format=hjson` is set. Hjson only changes the surface
+/// syntax (comments, unquoted keys, trailing commas); the resulting value still
+/// flows through the same `json_to_val` type-guided conversion.
+fn parse_call_request(
+    content_type: &str,
+    query: &HashMap<String, String>,
+    body: &str,
+) -> anyhow::Result<CallRequest> {
+    let is_hjson = query.get("format").map(String::as_str) == Some("hjson")
+        || content_type.starts_with("application/hjson");
+
+    if is_hjson {
+        deser_hjson::from_str(body).context("Invalid Hjson request body")
+    } else {
+        serde_json::from_str(body).context("Invalid JSON request body")
+    }
+}
+
+async fn openapi(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<JsonValue>, AppError> {
+    let mut openapi = build_openapi_doc(&state.doc)?;
+    if is_canonical(&query) {
+        canonicalize(&mut openapi);
+    }
     Ok(Json(openapi))
 }
 
+async fn rpc(State(state): State<Arc<AppState>>, body: String) -> axum::response::Response {
+    let payload = match serde_json::from_str::<JsonValue>(&body) {
+        Ok(payload) => payload,
+        Err(err) => {
+            let response = json!({
+                "jsonrpc": "2.0",
+                "error": { "code": jsonrpc::parse_error_code(), "message": err.to_string() },
+                "id": JsonValue::Null,
+            });
+            return Json(response).into_response();
+        }
+    };
+    match jsonrpc::dispatch(&state.engine, &state.instance_pre, &state.doc, payload) {
+        Some(body) => ([(CONTENT_TYPE, "application/json")], body).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// Whether `?canonical=1` requested deterministic, lexicographically-sorted
+/// output for stable diffing and content-hash caching.
+fn is_canonical(query: &HashMap<String, String>) -> bool {
+    query.get("canonical").map(String::as_str) == Some("1")
+}
+
+/// Recursively sort every object's keys lexicographically.
+fn canonicalize(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(map) => {
+            let mut entries: Vec<_> = std::mem::take(map).into_iter().collect();
+            for (_, v) in entries.iter_mut() {
+                canonicalize(v);
+            }
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            *map = entries.into_iter().collect();
+        }
+        JsonValue::Array(items) => items.iter_mut().for_each(canonicalize),
+        _ => {}
+    }
+}
+
 fn results_to_json(result_vals: &[Val]) -> JsonValue {
-    if result_vals.is_empty() {
-        json!({})
-    } else if result_vals.len() == 1 {
-        let val = &result_vals[0];
-        let json = val_to_json(val);
-        if let Val::Result(_) = val {
-            return json;
+    results_envelope(result_vals, val_to_json)
+}
+
+/// Build the `{}` / `{"result": …}` / `{"results": […]}` response envelope,
+/// converting each result value with `f`. A single `result`/`error` value is
+/// emitted bare, matching the tagged JSON used for `result` types.
+fn results_envelope(result_vals: &[Val], f: impl Fn(&Val) -> JsonValue) -> JsonValue {
+    let jsons = result_vals.iter().map(f).collect();
+    envelope_from(result_vals, jsons)
+}
+
+/// Wrap already-converted result JSON in the response envelope.
+fn envelope_from(result_vals: &[Val], mut jsons: Vec<JsonValue>) -> JsonValue {
+    match result_vals {
+        [] => json!({}),
+        [val] => {
+            let json = jsons.remove(0);
+            if let Val::Result(_) = val {
+                json
+            } else {
+                json!({ "result": json })
+            }
         }
-        json!({ "result": json })
-    } else {
-        let results: Vec<_> = result_vals.iter().map(val_to_json).collect();
-        json!({ "results": results })
+        _ => json!({ "results": jsons }),
     }
 }
 
@@ -0,0 +1,273 @@
+//! A JSON-RPC 2.0 surface over a component's default interface.
+//!
+//! [`dispatch`] parses a request object (or a batch array), resolves `method`
+//! against the world's default interface functions, maps `params` onto the
+//! function's parameter list, invokes the component, and wraps the outcome in a
+//! JSON-RPC response. Notifications (requests without an `id`) are executed but
+//! produce no response.
+
+use anyhow::{Context, Result};
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use serde_json::{json, Value as JsonValue};
+use wasmtime::{
+    component::{InstancePre, Val},
+    Engine, Store,
+};
+use wit_parser::{Document, Function};
+
+use crate::{json_to_val, serde::ValSerialize, TypeExt};
+
+// Standard JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Dispatch a single request or a batch. Returns `None` when there is nothing
+/// to send back (a lone notification, or a batch of only notifications);
+/// otherwise the response is serialized to JSON text so that record fields in
+/// the result keep their WIT declaration order (see [`ResultPayload`]).
+pub fn dispatch(
+    engine: &Engine,
+    instance_pre: &InstancePre<()>,
+    doc: &Document,
+    payload: JsonValue,
+) -> Option<String> {
+    let response = match payload {
+        JsonValue::Array(requests) => {
+            if requests.is_empty() {
+                return Some(to_json(&RpcResponse::error(
+                    JsonValue::Null,
+                    RpcError::new(INVALID_REQUEST, "empty batch"),
+                )));
+            }
+            let responses: Vec<_> = requests
+                .into_iter()
+                .filter_map(|request| handle_one(engine, instance_pre, doc, request))
+                .collect();
+            return (!responses.is_empty()).then(|| to_json(&responses));
+        }
+        request => handle_one(engine, instance_pre, doc, request)?,
+    };
+    Some(to_json(&response))
+}
+
+/// Serialize a response (or batch) to JSON text. The streaming serializer keeps
+/// map entries in insertion order, which is what carries record fields through
+/// in WIT order; serialization is infallible for these shapes.
+fn to_json<T: Serialize>(response: &T) -> String {
+    serde_json::to_string(response).expect("JSON-RPC response serializes")
+}
+
+fn handle_one(
+    engine: &Engine,
+    instance_pre: &InstancePre<()>,
+    doc: &Document,
+    request: JsonValue,
+) -> Option<RpcResponse> {
+    let JsonValue::Object(mut object) = request else {
+        return Some(RpcResponse::error(
+            JsonValue::Null,
+            RpcError::new(INVALID_REQUEST, "request must be an object"),
+        ));
+    };
+
+    // A request with no `id` member is a notification: we run it for its
+    // side effects but never respond, even on error.
+    let id = object.remove("id");
+    let is_notification = id.is_none();
+    let id = id.unwrap_or(JsonValue::Null);
+
+    let result = invoke(engine, instance_pre, doc, &mut object);
+
+    if is_notification {
+        return None;
+    }
+    Some(match result {
+        Ok(result_vals) => RpcResponse::Success { id, result_vals },
+        Err(err) => RpcResponse::error(id, err),
+    })
+}
+
+fn invoke(
+    engine: &Engine,
+    instance_pre: &InstancePre<()>,
+    doc: &Document,
+    object: &mut serde_json::Map<String, JsonValue>,
+) -> Result<Vec<Val>, RpcError> {
+    if object.get("jsonrpc").and_then(JsonValue::as_str) != Some("2.0") {
+        return Err(RpcError::new(INVALID_REQUEST, "missing or invalid jsonrpc version"));
+    }
+    let method = object
+        .get("method")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_REQUEST, "missing method"))?;
+
+    let func = lookup_function(doc, method)
+        .map_err(|_| RpcError::new(INTERNAL_ERROR, "could not resolve default interface"))?
+        .ok_or_else(|| RpcError::new(METHOD_NOT_FOUND, format!("no such method {method:?}")))?;
+
+    let args = order_params(func, object.remove("params"))?;
+
+    let result_vals = call_component(engine, instance_pre, method, args)
+        .map_err(|err| RpcError::new(INTERNAL_ERROR, format!("{err:#}")))?;
+
+    Ok(result_vals)
+}
+
+/// Resolve `params` into a positional JSON list matching `func.params` order.
+/// An array is positional; an object is keyed by parameter name.
+fn order_params(func: &Function, params: Option<JsonValue>) -> Result<Vec<JsonValue>, RpcError> {
+    match params.unwrap_or_else(|| JsonValue::Array(vec![])) {
+        JsonValue::Array(values) => {
+            if values.len() != func.params.len() {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    format!("expected {} params, got {}", func.params.len(), values.len()),
+                ));
+            }
+            Ok(values)
+        }
+        JsonValue::Object(mut map) => func
+            .params
+            .iter()
+            .map(|(name, _)| {
+                map.remove(name).ok_or_else(|| {
+                    RpcError::new(INVALID_PARAMS, format!("missing param {name:?}"))
+                })
+            })
+            .collect(),
+        _ => Err(RpcError::new(INVALID_PARAMS, "params must be an array or object")),
+    }
+}
+
+fn call_component(
+    engine: &Engine,
+    instance_pre: &InstancePre<()>,
+    method: &str,
+    args: Vec<JsonValue>,
+) -> Result<Vec<Val>> {
+    let mut store = Store::new(engine, ());
+    let instance = instance_pre.instantiate(&mut store)?;
+
+    let func = instance
+        .exports(&mut store)
+        .root()
+        .func(method)
+        .with_context(|| format!("No such export {method:?}"))?;
+
+    let param_types = func.params(&store);
+    let mut arg_vals = Vec::with_capacity(param_types.len());
+    for (param_type, arg_json) in param_types.iter().zip(args) {
+        arg_vals.push(json_to_val(param_type, arg_json)?);
+    }
+
+    let mut result_vals = func
+        .results(&store)
+        .into_vec()
+        .into_iter()
+        .map(|ty| ty.default_val())
+        .collect::<Result<Vec<_>>>()?;
+
+    let func = instance
+        .get_func(&mut store, method)
+        .context("Instance missing function")?;
+    func.call(&mut store, &arg_vals, &mut result_vals)?;
+
+    Ok(result_vals)
+}
+
+fn lookup_function<'a>(doc: &'a Document, method: &str) -> Result<Option<&'a Function>> {
+    let world = &doc.worlds[doc.default_world()?];
+    let iface = &doc.interfaces[world
+        .default
+        .context("world has no default interface")?];
+    Ok(iface.functions.iter().find(|func| func.name == method))
+}
+
+/// A single JSON-RPC response. It serializes itself through the streaming
+/// [`ValSerialize`] path so that records in a successful result keep their WIT
+/// field order, which a `serde_json::Value` envelope would sort away.
+enum RpcResponse {
+    Success { id: JsonValue, result_vals: Vec<Val> },
+    Error(JsonValue),
+}
+
+impl RpcResponse {
+    fn error(id: JsonValue, err: RpcError) -> Self {
+        Self::Error(json!({
+            "jsonrpc": "2.0",
+            "error": { "code": err.code, "message": err.message },
+            "id": id,
+        }))
+    }
+}
+
+impl Serialize for RpcResponse {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RpcResponse::Success { id, result_vals } => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("jsonrpc", "2.0")?;
+                map.serialize_entry("result", &ResultPayload(result_vals))?;
+                map.serialize_entry("id", id)?;
+                map.end()
+            }
+            RpcResponse::Error(value) => value.serialize(serializer),
+        }
+    }
+}
+
+/// The `result` member of a success response: `null` for no results, the lone
+/// value unwrapped, or an array otherwise. Each value streams through
+/// [`ValSerialize`] so record fields stay in declaration order.
+struct ResultPayload<'a>(&'a [Val]);
+
+impl Serialize for ResultPayload<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            [] => serializer.serialize_unit(),
+            [val] => val.serialize(serializer),
+            vals => {
+                let mut seq = serializer.serialize_seq(Some(vals.len()))?;
+                for val in vals {
+                    seq.serialize_element(&ValEntry(val))?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+/// Adapts a single [`Val`] to [`Serialize`] for use as a sequence element.
+struct ValEntry<'a>(&'a Val);
+
+impl Serialize for ValEntry<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// The parse-error code, surfaced by transports that fail to decode the body
+/// before it reaches [`dispatch`].
+pub const fn parse_error_code() -> i64 {
+    PARSE_ERROR
+}
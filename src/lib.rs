@@ -1,4 +1,6 @@
+pub mod jsonrpc;
 pub mod openapi;
+pub mod scale;
 pub mod serde;
 mod type_ext;
 
@@ -12,14 +14,14 @@ pub fn json_to_val(ty: &Type, json: JsonValue) -> Result<Val> {
     use serde_json::from_value;
     Ok(match ty {
         Type::Bool => Val::Bool(from_value(json)?),
-        Type::U8 => Val::U8(from_value(json)?),
-        Type::U16 => Val::U16(from_value(json)?),
-        Type::U32 => Val::U32(from_value(json)?),
-        Type::U64 => Val::U64(from_value(json)?),
-        Type::S8 => Val::S8(from_value(json)?),
-        Type::S16 => Val::S16(from_value(json)?),
-        Type::S32 => Val::S32(from_value(json)?),
-        Type::S64 => Val::S64(from_value(json)?),
+        Type::U8 => Val::U8(json_to_int(json)?),
+        Type::U16 => Val::U16(json_to_int(json)?),
+        Type::U32 => Val::U32(json_to_int(json)?),
+        Type::U64 => Val::U64(json_to_int(json)?),
+        Type::S8 => Val::S8(json_to_int(json)?),
+        Type::S16 => Val::S16(json_to_int(json)?),
+        Type::S32 => Val::S32(json_to_int(json)?),
+        Type::S64 => Val::S64(json_to_int(json)?),
         Type::Float32 => {
             let value = match json.as_str() {
                 Some("NaN") => f32::NAN,
@@ -143,6 +145,13 @@ pub fn json_to_val(ty: &Type, json: JsonValue) -> Result<Val> {
     })
 }
 
+/// Convert a [`Val`] to a [`JsonValue`].
+///
+/// The returned `Value` does not carry a guaranteed key order: a
+/// `serde_json::Map` only preserves insertion order with the `preserve_order`
+/// feature, and sorts alphabetically otherwise. Callers that need records in
+/// WIT declaration order should serialize straight to text with
+/// [`to_json_string`], which streams fields in order regardless of the feature.
 pub fn val_to_json(val: &Val) -> JsonValue {
     match val {
         &Val::Bool(v) => v.into(),
@@ -227,6 +236,258 @@ pub fn val_to_json(val: &Val) -> JsonValue {
     }
 }
 
+/// Serialize a [`Val`] to a JSON string with record fields in WIT declaration
+/// order.
+///
+/// [`val_to_json`] builds a `serde_json::Value`, whose object key order depends
+/// on serde_json's `preserve_order` feature. This instead streams through
+/// [`ValSerialize`](crate::serde::ValSerialize), which writes fields in the
+/// order they are emitted, so the declaration order holds unconditionally.
+pub fn to_json_string(val: &Val) -> serde_json::Result<String> {
+    use crate::serde::ValSerialize;
+
+    let mut buf = Vec::new();
+    val.serialize(&mut serde_json::Serializer::new(&mut buf))?;
+    // serde_json only ever writes UTF-8.
+    Ok(String::from_utf8(buf).expect("serde_json emits UTF-8"))
+}
+
+/// Marker key identifying an out-of-band binary attachment in the JSON
+/// envelope produced by [`to_value`].
+const PLACEHOLDER_KEY: &str = "_placeholder";
+
+/// Like [`val_to_json`], but emit `list<u8>` values as base64 strings instead
+/// of arrays of integers. The [`TypeWrapper`](serde::TypeWrapper) deserializer
+/// already accepts base64 on input, so this round-trips. Only byte lists reached
+/// through lists, records and tuples are converted; one inside a variant,
+/// option, result or union is rendered by [`val_to_json`] as an integer array.
+///
+/// Like [`val_to_json`], this builds a `serde_json::Value`, so record fields
+/// come out sorted rather than in WIT declaration order. The binary formats
+/// trade field order for compact byte transport; order-sensitive callers use
+/// the default (inline) response, which streams through [`to_json_string`].
+pub fn val_to_json_base64(val: &Val) -> JsonValue {
+    match val {
+        Val::List(list) => match as_byte_list(list) {
+            Some(bytes) => base64::encode(bytes).into(),
+            None => JsonValue::Array(list.iter().map(val_to_json_base64).collect()),
+        },
+        Val::Record(record) => {
+            let mut map = serde_json::Map::new();
+            for (key, val) in record.fields() {
+                map.insert(key.to_string(), val_to_json_base64(val));
+            }
+            JsonValue::Object(map)
+        }
+        Val::Tuple(tuple) => {
+            JsonValue::Array(tuple.values().iter().map(val_to_json_base64).collect())
+        }
+        _ => val_to_json(val),
+    }
+}
+
+/// Serialize `val` to JSON, lifting each `list<u8>` reached through lists,
+/// records and tuples out of the document as a binary attachment. The returned
+/// JSON carries `{"_placeholder":true,"num":n}` markers indexing into the
+/// accompanying buffer, so bulk binary data ships beside the envelope rather
+/// than inflating it into number arrays. A byte list inside a variant, option,
+/// result or union is not lifted; [`val_to_json`] renders it as an integer array.
+///
+/// Like [`val_to_json`], the returned `Value` sorts record fields rather than
+/// keeping WIT declaration order; the attachment format optimizes for byte
+/// transport, not for field order.
+pub fn to_value(val: &Val) -> (JsonValue, Vec<Vec<u8>>) {
+    let (mut jsons, attachments) = to_values(std::slice::from_ref(val));
+    (jsons.remove(0), attachments)
+}
+
+fn to_value_inner(val: &Val, attachments: &mut Vec<Vec<u8>>) -> JsonValue {
+    match val {
+        Val::List(list) => match as_byte_list(list) {
+            Some(bytes) => {
+                let num = attachments.len();
+                attachments.push(bytes);
+                let mut map = serde_json::Map::new();
+                map.insert(PLACEHOLDER_KEY.to_string(), JsonValue::Bool(true));
+                map.insert("num".to_string(), num.into());
+                JsonValue::Object(map)
+            }
+            None => JsonValue::Array(
+                list.iter().map(|v| to_value_inner(v, attachments)).collect(),
+            ),
+        },
+        Val::Record(record) => {
+            let mut map = serde_json::Map::new();
+            for (key, val) in record.fields() {
+                map.insert(key.to_string(), to_value_inner(val, attachments));
+            }
+            JsonValue::Object(map)
+        }
+        Val::Tuple(tuple) => JsonValue::Array(
+            tuple
+                .values()
+                .iter()
+                .map(|v| to_value_inner(v, attachments))
+                .collect(),
+        ),
+        _ => val_to_json(val),
+    }
+}
+
+/// Serialize several values into one JSON document sharing a single attachment
+/// buffer, so placeholder indices stay unique across the whole document (e.g. a
+/// function's result list). [`to_value`] is the single-value case.
+pub fn to_values(vals: &[Val]) -> (Vec<JsonValue>, Vec<Vec<u8>>) {
+    let mut attachments = Vec::new();
+    let jsons = vals
+        .iter()
+        .map(|val| to_value_inner(val, &mut attachments))
+        .collect();
+    (jsons, attachments)
+}
+
+/// The inverse of [`to_value`]: convert JSON back into a `Val`, re-hydrating
+/// placeholder markers and base64 strings for `list<u8>` from `attachments`.
+/// Everything else follows the same type-guided rules as [`json_to_val`].
+pub fn from_value(ty: &Type, json: JsonValue, attachments: &[Vec<u8>]) -> Result<Val> {
+    match ty {
+        Type::List(list) if list.ty() == Type::U8 => {
+            let bytes = match json {
+                JsonValue::Object(map) if map.get(PLACEHOLDER_KEY) == Some(&JsonValue::Bool(true)) => {
+                    let num = map
+                        .get("num")
+                        .and_then(JsonValue::as_u64)
+                        .context("binary placeholder missing num")?
+                        as usize;
+                    attachments
+                        .get(num)
+                        .with_context(|| format!("no attachment for placeholder {num}"))?
+                        .clone()
+                }
+                JsonValue::String(s) => base64::decode(s)?,
+                other => return json_to_val(ty, other),
+            };
+            list.new_val(bytes.into_iter().map(Val::U8).collect())?
+        }
+        Type::List(list) => {
+            let JsonValue::Array(json_array) = json else {
+                bail!("Cannot deserialize {json:?} into list");
+            };
+            let values = json_array
+                .into_iter()
+                .map(|item| from_value(&list.ty(), item, attachments))
+                .collect::<Result<_>>()?;
+            list.new_val(values)?
+        }
+        Type::Record(record) => {
+            let JsonValue::Object(mut json_object) = json else {
+                bail!("Cannot deserialize {json:?} into record");
+            };
+            let values = record
+                .fields()
+                .map(|field| {
+                    let field_value = json_object.remove(field.name).unwrap_or(JsonValue::Null);
+                    Ok((field.name, from_value(&field.ty, field_value, attachments)?))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            record.new_val(values)?
+        }
+        Type::Tuple(tuple) => {
+            let JsonValue::Array(json_array) = json else {
+                bail!("Cannot deserialize {json:?} into tuple");
+            };
+            ensure!(
+                json_array.len() == tuple.types().len(),
+                "tuple length mismatch"
+            );
+            let values = tuple
+                .types()
+                .zip(json_array)
+                .map(|(ty, json_value)| from_value(&ty, json_value, attachments))
+                .collect::<Result<_>>()?;
+            tuple.new_val(values)?
+        }
+        Type::Variant(variant) => {
+            let (key, json_value) =
+                get_single_entry_json(json).context("Couldn't deserialize into variant")?;
+            let case = variant
+                .cases()
+                .find(|case| case.name == key)
+                .with_context(|| format!("No variant case named {key:?}"))?;
+            let value = case
+                .ty
+                .map(|ty| from_value(&ty, json_value, attachments))
+                .transpose()?;
+            variant.new_val(case.name, value)?
+        }
+        Type::Option(option) => {
+            let value = if json.is_null() {
+                None
+            } else {
+                Some(from_value(&option.ty(), json, attachments)?)
+            };
+            option.new_val(value)?
+        }
+        Type::Result(result) => {
+            let (key, json_value) =
+                get_single_entry_json(json).context("Couldn't deserialize into result")?;
+            let value = match key.as_str() {
+                "result" => Ok(result
+                    .ok()
+                    .map(|ty| from_value(&ty, json_value, attachments))
+                    .transpose()?),
+                "error" => Err(result
+                    .err()
+                    .map(|ty| from_value(&ty, json_value, attachments))
+                    .transpose()?),
+                _ => bail!("Invalid key {key:?} for result"),
+            };
+            result.new_val(value)?
+        }
+        // Scalars, enums, and flags carry no nested byte lists. Unions also go
+        // through `json_to_val`, so a `list<u8>` inside one is read only from
+        // an array of integers.
+        _ => json_to_val(ty, json)?,
+    }
+}
+
+/// Collect a `list<u8>` value into a byte vector, or `None` if the list holds
+/// any non-`u8` element.
+///
+/// An empty list also yields `None`: only the elements are inspected, so an
+/// empty `list<u8>` is not told apart from, say, an empty `list<string>` and is
+/// rendered as the neutral `[]` rather than an empty base64 string. The
+/// type-guided [`from_value`] reads `[]` straight back into an empty
+/// `list<u8>`, so the round-trip still holds.
+fn as_byte_list(list: &wasmtime::component::List) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(list.len());
+    for val in list.iter() {
+        match val {
+            Val::U8(b) => bytes.push(*b),
+            _ => return None,
+        }
+    }
+    (!bytes.is_empty()).then_some(bytes)
+}
+
+/// Convert a JSON scalar into an integer type. A JSON number is taken directly;
+/// a JSON string is parsed as a decimal integer. The string form losslessly
+/// covers the full width of the 64-bit types, which JSON numbers can only carry
+/// through an f64 that most consumers truncate, so `"18446744073709551615"`
+/// reaches `u64::MAX`.
+fn json_to_int<T>(json: JsonValue) -> Result<T>
+where
+    T: std::str::FromStr + ::serde::de::DeserializeOwned,
+    T::Err: std::fmt::Display,
+{
+    match json {
+        JsonValue::String(s) => s
+            .parse()
+            .map_err(|err| anyhow::anyhow!("Invalid integer {s:?}: {err}")),
+        other => Ok(serde_json::from_value(other)?),
+    }
+}
+
 fn get_single_entry_json(json: JsonValue) -> Result<(String, JsonValue)> {
     let JsonValue::Object(object) = json else {
         bail!("expected object, got {json:?}");
@@ -242,3 +503,18 @@ fn option_val_to_json(val: Option<&Val>) -> JsonValue {
         None => JsonValue::Null,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_to_val_reads_decimal_string_integers() -> Result<()> {
+        // The `/call` and `/rpc` endpoints both feed JSON through
+        // `json_to_val`, so a decimal string must reach the full `u64` range
+        // that a JSON number cannot carry.
+        let val = json_to_val(&Type::U64, JsonValue::from("18446744073709551615"))?;
+        assert_eq!(val, Val::U64(u64::MAX));
+        Ok(())
+    }
+}
@@ -1,13 +1,17 @@
-use std::any::{type_name, Any};
-
 use anyhow::{Context, Result};
-use serde::de::DeserializeOwned;
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use serde_json::Value as JsonValue;
 use wasmtime::{
     component::{Component, Linker, Val},
     Config, Engine, Store,
 };
 use wit_component::DocumentPrinter;
 
+use component_rpc::{json_to_val, TypeExt};
+
 const USAGE: &str = "component-rpc <path>";
 
 #[tokio::main]
@@ -42,58 +46,35 @@ async fn main() -> Result<()> {
     let doc_str = DocumentPrinter::default().print(&doc)?;
     println!("Parsed world:\n{doc_str}");
 
-    use wit_parser::Type::*;
+    let engine = Engine::new(Config::new().wasm_component_model(true))?;
+    let component = Component::new(&engine, &wasm)?;
+    let linker = Linker::new(&engine);
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+
+    let func = instance
+        .exports(&mut store)
+        .root()
+        .func(&func_name)
+        .context("Instance missing function")?;
 
-    let mut arg_vals: Vec<Val> = vec![];
-    for (name, ty) in &func_type.params {
+    let mut arg_vals: Vec<Val> = Vec::new();
+    for (idx, param_type) in func.params(&store).iter().enumerate() {
         let arg_str = args
             .next()
-            .with_context(|| format!("Missing argument for parameter {name:?}"))?;
-
-        arg_vals.push(match *ty {
-            Bool => todo!(),
-            U8 => todo!(),
-            U16 => todo!(),
-            U32 => Val::U32(deserialize_arg(&arg_str)?),
-            U64 => todo!(),
-            S8 => todo!(),
-            S16 => todo!(),
-            S32 => todo!(),
-            S64 => todo!(),
-            Float32 => todo!(),
-            Float64 => todo!(),
-            Char => todo!(),
-            String => todo!(),
-            Id(_) => todo!(),
-        });
+            .with_context(|| format!("Missing argument for parameter {idx}"))?;
+        let arg_json: JsonValue = serde_json::from_str(&arg_str)
+            .with_context(|| format!("Failed to parse argument {idx} as JSON: {arg_str:?}"))?;
+        arg_vals.push(json_to_val(param_type, arg_json)?);
     }
 
-    let mut result_vals: Vec<Val> = vec![];
-    match func_type.results {
-        wit_parser::Results::Named(_) => todo!(),
-        wit_parser::Results::Anon(ty) => result_vals.push(match ty {
-            Bool => todo!(),
-            U8 => todo!(),
-            U16 => todo!(),
-            U32 => Val::U32(0),
-            U64 => todo!(),
-            S8 => todo!(),
-            S16 => todo!(),
-            S32 => todo!(),
-            S64 => todo!(),
-            Float32 => todo!(),
-            Float64 => todo!(),
-            Char => todo!(),
-            String => todo!(),
-            Id(_) => todo!(),
-        }),
-    }
+    let mut result_vals = func
+        .results(&store)
+        .into_vec()
+        .into_iter()
+        .map(|ty| ty.default_val())
+        .collect::<Result<Vec<_>>>()?;
 
-    let engine = Engine::new(Config::new().wasm_component_model(true))?;
-    let component = Component::new(&engine, &wasm)?;
-    let linker = Linker::new(&engine);
-    let mut store = Store::new(&engine, ());
-    let instance = linker.instantiate(&mut store, &component)?;
     let func = instance
         .get_func(&mut store, &func_name)
         .context("Instance missing function")?;
@@ -102,14 +83,54 @@ async fn main() -> Result<()> {
 
     func.call(&mut store, &arg_vals, &mut result_vals)?;
 
-    println!("Results: {result_vals:?}");
+    let output = RenderResults {
+        results: &func_type.results,
+        vals: &result_vals,
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
 
     Ok(())
 }
 
-fn deserialize_arg<T: Any + DeserializeOwned>(arg: &str) -> Result<T> {
-    serde_json::from_str(arg).with_context(|| {
-        let type_name = type_name::<T>();
-        format!("Failed to parse {arg:?} as a {type_name:?}")
-    })
+/// Render a function's results as JSON: named results become an object keyed by
+/// result name, a single anonymous result is rendered directly. Each value
+/// streams through [`ValSerialize`](component_rpc::serde::ValSerialize) so
+/// record fields keep their WIT declaration order, which a `serde_json::Value`
+/// would sort away.
+struct RenderResults<'a> {
+    results: &'a wit_parser::Results,
+    vals: &'a [Val],
+}
+
+impl Serialize for RenderResults<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.results {
+            wit_parser::Results::Named(names) => {
+                let mut map = serializer.serialize_map(Some(names.len()))?;
+                for ((name, _), val) in names.iter().zip(self.vals) {
+                    map.serialize_entry(name, &ValEntry(val))?;
+                }
+                map.end()
+            }
+            wit_parser::Results::Anon(_) => match self.vals {
+                [val] => ValEntry(val).serialize(serializer),
+                vals => {
+                    let mut seq = serializer.serialize_seq(Some(vals.len()))?;
+                    for val in vals {
+                        seq.serialize_element(&ValEntry(val))?;
+                    }
+                    seq.end()
+                }
+            },
+        }
+    }
+}
+
+/// Adapts a single [`Val`] to [`Serialize`] via its [`ValSerialize`] impl.
+struct ValEntry<'a>(&'a Val);
+
+impl Serialize for ValEntry<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        component_rpc::serde::ValSerialize::serialize(self.0, serializer)
+    }
 }
@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
-use wit_parser::{Document, Function, Type};
+use wit_parser::{Document, Function, Type, TypeDefKind};
 
 pub fn build_openapi_doc(doc: &Document) -> Result<Value> {
     let world = &doc.worlds[doc.default_world()?];
@@ -16,7 +16,12 @@ pub fn build_openapi_doc(doc: &Document) -> Result<Value> {
     let component_schemas = Value::Object(
         doc.types
             .iter()
-            .map(|(id, def)| (format!("typedef-{}", id.index()), json!({})))
+            .map(|(id, def)| {
+                (
+                    format!("typedef-{}", id.index()),
+                    build_typedef_schema(&def.kind),
+                )
+            })
             .collect(),
     );
 
@@ -40,10 +45,12 @@ fn build_openapi_path(func: &Function) -> Result<(String, Value)> {
         0 => json!({}),
         1 => {
             let ty = func.results.iter_types().next().unwrap();
-
-            json!({"result": 1})
+            json!({ "result": build_type_schema(ty) })
+        }
+        _ => {
+            let types: Vec<_> = func.results.iter_types().collect();
+            json!({ "results": build_tuple_schema(&types) })
         }
-        _ => json!({"results": 2}),
     };
 
     let item = json!({
@@ -77,8 +84,11 @@ fn build_openapi_path(func: &Function) -> Result<(String, Value)> {
     Ok((format!("/call/{}", func.name), item))
 }
 
-fn build_tuple_schema(types: &[&Type]) -> Value {
-    let items: Vec<Value> = types.iter().map(|ty| build_type_schema(ty)).collect();
+fn build_tuple_schema<T: std::borrow::Borrow<Type>>(types: &[T]) -> Value {
+    let items: Vec<Value> = types
+        .iter()
+        .map(|ty| build_type_schema(ty.borrow()))
+        .collect();
     json!({
         "type": "array",
         "minItems": items.len(),
@@ -87,6 +97,85 @@ fn build_tuple_schema(types: &[&Type]) -> Value {
     })
 }
 
+/// Schema for a single type. Type definitions are emitted once under
+/// `components/schemas` and referenced here by `$ref`.
 fn build_type_schema(ty: &Type) -> Value {
-    json!({})
+    match ty {
+        Type::Bool => json!({ "type": "boolean" }),
+        Type::U8 | Type::U16 | Type::U32 | Type::U64 => json!({ "type": "integer", "minimum": 0 }),
+        Type::S8 | Type::S16 | Type::S32 | Type::S64 => json!({ "type": "integer" }),
+        Type::Float32 | Type::Float64 => json!({ "type": "number" }),
+        Type::Char | Type::String => json!({ "type": "string" }),
+        Type::Id(id) => json!({ "$ref": format!("#/components/schemas/typedef-{}", id.index()) }),
+    }
+}
+
+/// Schema for a type definition, matching the JSON shape that
+/// [`val_to_json`](crate::val_to_json) produces for the corresponding value.
+fn build_typedef_schema(kind: &TypeDefKind) -> Value {
+    match kind {
+        TypeDefKind::Record(record) => {
+            let properties: serde_json::Map<_, _> = record
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), build_type_schema(&field.ty)))
+                .collect();
+            let required: Vec<_> = record.fields.iter().map(|field| field.name.clone()).collect();
+            json!({ "type": "object", "properties": properties, "required": required })
+        }
+        TypeDefKind::Tuple(tuple) => build_tuple_schema(&tuple.types),
+        TypeDefKind::List(ty) => json!({ "type": "array", "items": build_type_schema(ty) }),
+        TypeDefKind::Option(ty) => {
+            json!({ "anyOf": [build_type_schema(ty), { "type": "null" }] })
+        }
+        TypeDefKind::Result(result) => {
+            let ok = payload_schema(&result.ok);
+            let err = payload_schema(&result.err);
+            json!({ "oneOf": [single_key("result", ok), single_key("error", err)] })
+        }
+        TypeDefKind::Variant(variant) => {
+            let cases: Vec<Value> = variant
+                .cases
+                .iter()
+                .map(|case| single_key(&case.name, payload_schema(&case.ty)))
+                .collect();
+            json!({ "oneOf": cases })
+        }
+        TypeDefKind::Union(union) => {
+            let cases: Vec<Value> = union
+                .cases
+                .iter()
+                .enumerate()
+                .map(|(idx, case)| single_key(&idx.to_string(), build_type_schema(&case.ty)))
+                .collect();
+            json!({ "oneOf": cases })
+        }
+        TypeDefKind::Enum(enum_) => {
+            let names: Vec<_> = enum_.cases.iter().map(|case| case.name.clone()).collect();
+            json!({ "type": "string", "enum": names })
+        }
+        TypeDefKind::Flags(flags) => {
+            let names: Vec<_> = flags.flags.iter().map(|flag| flag.name.clone()).collect();
+            json!({ "type": "array", "items": { "type": "string", "enum": names } })
+        }
+        TypeDefKind::Type(ty) => build_type_schema(ty),
+        _ => json!({}),
+    }
+}
+
+/// A single-key object schema mirroring the tagged JSON emitted for variants,
+/// unions, and results.
+fn single_key(key: &str, schema: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": { key: schema },
+        "required": [key],
+    })
+}
+
+fn payload_schema(ty: &Option<Type>) -> Value {
+    match ty {
+        Some(ty) => build_type_schema(ty),
+        None => json!({ "type": "null" }),
+    }
 }
@@ -0,0 +1,381 @@
+//! A binary [SCALE] codec for component values, guided by the WIT [`Type`].
+//!
+//! This mirrors the [`json_to_val`]/[`val_to_json`] pair as
+//! [`scale_to_val`]/[`val_to_scale`], trading JSON's readability for a compact,
+//! lossless wire format. Unlike JSON, SCALE carries no field names or tags, so
+//! decoding is entirely type-driven.
+//!
+//! [SCALE]: https://docs.substrate.io/reference/scale-codec/
+//! [`json_to_val`]: crate::json_to_val
+//! [`val_to_json`]: crate::val_to_json
+
+use anyhow::{bail, ensure, Result};
+use wasmtime::component::{Type, Val};
+
+/// Encode a single value to SCALE bytes.
+pub fn val_to_scale(val: &Val) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    encode_val(&mut buf, val)?;
+    Ok(buf)
+}
+
+/// Decode a single value of the given type from SCALE bytes, requiring the
+/// whole slice to be consumed.
+pub fn scale_to_val(ty: &Type, bytes: &[u8]) -> Result<Val> {
+    let mut decoder = Decoder::new(bytes);
+    let val = decoder.decode(ty)?;
+    ensure!(decoder.is_empty(), "trailing bytes after SCALE value");
+    Ok(val)
+}
+
+fn encode_val(buf: &mut Vec<u8>, val: &Val) -> Result<()> {
+    match val {
+        Val::Bool(v) => buf.push(*v as u8),
+        Val::U8(v) => buf.push(*v),
+        Val::U16(v) => buf.extend(v.to_le_bytes()),
+        Val::U32(v) => buf.extend(v.to_le_bytes()),
+        Val::U64(v) => buf.extend(v.to_le_bytes()),
+        Val::S8(v) => buf.extend(v.to_le_bytes()),
+        Val::S16(v) => buf.extend(v.to_le_bytes()),
+        Val::S32(v) => buf.extend(v.to_le_bytes()),
+        Val::S64(v) => buf.extend(v.to_le_bytes()),
+        Val::Float32(v) => buf.extend(v.to_le_bytes()),
+        Val::Float64(v) => buf.extend(v.to_le_bytes()),
+        Val::Char(v) => buf.extend((*v as u32).to_le_bytes()),
+        Val::String(v) => {
+            encode_compact(buf, v.len() as u64);
+            buf.extend(v.as_bytes());
+        }
+
+        Val::List(list) => {
+            encode_compact(buf, list.len() as u64);
+            for val in list.iter() {
+                encode_val(buf, val)?;
+            }
+        }
+        Val::Record(record) => {
+            for (_, val) in record.fields() {
+                encode_val(buf, val)?;
+            }
+        }
+        Val::Tuple(tuple) => {
+            for val in tuple.values() {
+                encode_val(buf, val)?;
+            }
+        }
+        Val::Variant(variant) => {
+            let idx = case_index(variant.ty().cases().map(|case| case.name), variant.discriminant())?;
+            buf.push(idx);
+            if let Some(payload) = variant.payload() {
+                encode_val(buf, payload)?;
+            }
+        }
+        Val::Enum(enum_) => {
+            let idx = case_index(enum_.ty().names(), enum_.discriminant())?;
+            buf.push(idx);
+        }
+        Val::Union(union) => {
+            buf.push(u8::try_from(union.discriminant())?);
+            encode_val(buf, union.payload())?;
+        }
+        Val::Option(option) => match option.value() {
+            Some(val) => {
+                buf.push(1);
+                encode_val(buf, val)?;
+            }
+            None => buf.push(0),
+        },
+        Val::Result(result) => match result.value() {
+            Ok(val) => {
+                buf.push(0);
+                if let Some(val) = val {
+                    encode_val(buf, val)?;
+                }
+            }
+            Err(val) => {
+                buf.push(1);
+                if let Some(val) = val {
+                    encode_val(buf, val)?;
+                }
+            }
+        },
+        Val::Flags(flags) => {
+            let names: Vec<&str> = flags.ty().names().collect();
+            let mut bits = vec![0u8; names.len().div_ceil(8)];
+            for name in flags.flags() {
+                let idx = names
+                    .iter()
+                    .position(|n| *n == name)
+                    .expect("flag not present in its own type");
+                bits[idx / 8] |= 1 << (idx % 8);
+            }
+            buf.extend(bits);
+        }
+    }
+    Ok(())
+}
+
+fn case_index<'a>(names: impl Iterator<Item = &'a str>, name: &str) -> Result<u8> {
+    let idx = names
+        .position(|n| n == name)
+        .expect("discriminant not present in its own type");
+    Ok(u8::try_from(idx)?)
+}
+
+/// Encode a SCALE compact-length prefix. The low two bits of the first byte
+/// select the mode: single-byte (`< 64`), two-byte (`< 2^14`), four-byte
+/// (`< 2^30`), and big-integer otherwise.
+fn encode_compact(buf: &mut Vec<u8>, n: u64) {
+    if n < 0b1 << 6 {
+        buf.push((n as u8) << 2);
+    } else if n < 0b1 << 14 {
+        buf.extend((((n as u16) << 2) | 0b01).to_le_bytes());
+    } else if n < 0b1 << 30 {
+        buf.extend((((n as u32) << 2) | 0b10).to_le_bytes());
+    } else {
+        let mut bytes = n.to_le_bytes().to_vec();
+        while bytes.len() > 4 && *bytes.last().unwrap() == 0 {
+            bytes.pop();
+        }
+        buf.push((((bytes.len() - 4) as u8) << 2) | 0b11);
+        buf.extend(bytes);
+    }
+}
+
+/// A cursor over SCALE bytes driven by the expected [`Type`]. Decoding several
+/// values in sequence (e.g. a function's argument list) shares one decoder.
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Assert that the whole input has been consumed.
+    pub fn finish(&self) -> Result<()> {
+        ensure!(self.is_empty(), "trailing bytes after SCALE value");
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self.pos + n;
+        ensure!(end <= self.bytes.len(), "unexpected end of SCALE input");
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn compact(&mut self) -> Result<u64> {
+        let first = self.byte()?;
+        Ok(match first & 0b11 {
+            0b00 => (first >> 2) as u64,
+            0b01 => {
+                let hi = self.byte()?;
+                (u16::from_le_bytes([first, hi]) >> 2) as u64
+            }
+            0b10 => {
+                let rest = self.take(3)?;
+                let word = u32::from_le_bytes([first, rest[0], rest[1], rest[2]]);
+                (word >> 2) as u64
+            }
+            _ => {
+                let len = (first >> 2) as usize + 4;
+                ensure!(len <= 8, "SCALE compact integer too large for u64");
+                let mut value = [0u8; 8];
+                value[..len].copy_from_slice(self.take(len)?);
+                u64::from_le_bytes(value)
+            }
+        })
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("slice length checked"))
+    }
+
+    pub fn decode(&mut self, ty: &Type) -> Result<Val> {
+        Ok(match ty {
+            Type::Bool => Val::Bool(self.byte()? != 0),
+            Type::U8 => Val::U8(self.byte()?),
+            Type::U16 => Val::U16(u16::from_le_bytes(self.array()?)),
+            Type::U32 => Val::U32(u32::from_le_bytes(self.array()?)),
+            Type::U64 => Val::U64(u64::from_le_bytes(self.array()?)),
+            Type::S8 => Val::S8(i8::from_le_bytes(self.array()?)),
+            Type::S16 => Val::S16(i16::from_le_bytes(self.array()?)),
+            Type::S32 => Val::S32(i32::from_le_bytes(self.array()?)),
+            Type::S64 => Val::S64(i64::from_le_bytes(self.array()?)),
+            Type::Float32 => Val::Float32(u32::from_le_bytes(self.array()?)),
+            Type::Float64 => Val::Float64(u64::from_le_bytes(self.array()?)),
+            Type::Char => {
+                let code = u32::from_le_bytes(self.array()?);
+                let c = char::from_u32(code)
+                    .ok_or_else(|| anyhow::anyhow!("invalid char code {code}"))?;
+                Val::Char(c)
+            }
+            Type::String => {
+                let len = self.compact()? as usize;
+                let bytes = self.take(len)?;
+                Val::String(std::str::from_utf8(bytes)?.into())
+            }
+
+            Type::List(list) => {
+                let len = self.compact()? as usize;
+                let elem = list.ty();
+                let mut vals = Vec::with_capacity(len);
+                for _ in 0..len {
+                    vals.push(self.decode(&elem)?);
+                }
+                list.new_val(vals.into_boxed_slice())?
+            }
+            Type::Record(record) => {
+                let values = record
+                    .fields()
+                    .map(|field| Ok((field.name, self.decode(&field.ty)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                record.new_val(values)?
+            }
+            Type::Tuple(tuple) => {
+                let values = tuple
+                    .types()
+                    .map(|ty| self.decode(&ty))
+                    .collect::<Result<Vec<_>>>()?;
+                tuple.new_val(values.into_boxed_slice())?
+            }
+            Type::Variant(variant) => {
+                let idx = self.byte()? as usize;
+                let case = variant
+                    .cases()
+                    .nth(idx)
+                    .ok_or_else(|| anyhow::anyhow!("no variant case at index {idx}"))?;
+                let payload = case.ty.map(|ty| self.decode(&ty)).transpose()?;
+                variant.new_val(case.name, payload)?
+            }
+            Type::Enum(enum_) => {
+                let idx = self.byte()? as usize;
+                let name = enum_
+                    .names()
+                    .nth(idx)
+                    .ok_or_else(|| anyhow::anyhow!("no enum case at index {idx}"))?;
+                enum_.new_val(name)?
+            }
+            Type::Union(union) => {
+                let idx = self.byte()? as u32;
+                let ty = union
+                    .types()
+                    .nth(idx as usize)
+                    .ok_or_else(|| anyhow::anyhow!("no union case at index {idx}"))?;
+                let payload = self.decode(&ty)?;
+                union.new_val(idx, payload)?
+            }
+            Type::Option(option) => {
+                let value = match self.byte()? {
+                    0 => None,
+                    1 => Some(self.decode(&option.ty())?),
+                    other => bail!("invalid option discriminant {other}"),
+                };
+                option.new_val(value)?
+            }
+            Type::Result(result) => {
+                let value = match self.byte()? {
+                    0 => Ok(result.ok().map(|ty| self.decode(&ty)).transpose()?),
+                    1 => Err(result.err().map(|ty| self.decode(&ty)).transpose()?),
+                    other => bail!("invalid result discriminant {other}"),
+                };
+                result.new_val(value)?
+            }
+            Type::Flags(flags) => {
+                let names: Vec<&str> = flags.names().collect();
+                let bits = self.take(names.len().div_ceil(8))?;
+                let active: Vec<&str> = names
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| bits[idx / 8] & (1 << (idx % 8)) != 0)
+                    .map(|(_, name)| *name)
+                    .collect();
+                flags.new_val(&active)?
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compact_round_trip(n: u64) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_compact(&mut buf, n);
+        let mut decoder = Decoder::new(&buf);
+        assert_eq!(decoder.compact().unwrap(), n, "compact {n}");
+        assert!(decoder.is_empty(), "compact {n} left trailing bytes");
+        buf
+    }
+
+    // Every mode boundary is where an off-by-one in the length prefix hides.
+    #[test]
+    fn compact_covers_mode_boundaries() {
+        for n in [0, 1, 63, 64, 16_383, 16_384, (1 << 30) - 1, 1 << 30, u64::MAX] {
+            compact_round_trip(n);
+        }
+        // Known single/two-byte encodings anchor the bit layout.
+        assert_eq!(compact_round_trip(0), [0b0000_0000]);
+        assert_eq!(compact_round_trip(63), [0b1111_1100]);
+        assert_eq!(compact_round_trip(64), [0b0000_0001, 0b0000_0001]);
+    }
+
+    // Big-integer mode must drop trailing zero bytes so the length nibble is
+    // minimal; `1 << 32` needs five value bytes, not eight.
+    #[test]
+    fn compact_big_integer_trims_trailing_zeros() {
+        let bytes = compact_round_trip(1 << 32);
+        let len = (bytes[0] >> 2) as usize + 4;
+        assert_eq!(bytes[0] & 0b11, 0b11);
+        assert_eq!(len, 5);
+        assert_eq!(bytes.len(), 1 + len);
+    }
+
+    #[test]
+    fn scalars_round_trip() {
+        let cases = [
+            (Type::Bool, Val::Bool(true)),
+            (Type::U8, Val::U8(0xff)),
+            (Type::U64, Val::U64(u64::MAX)),
+            (Type::S64, Val::S64(-42)),
+            (Type::Float64, Val::Float64(1.5f64.to_bits())),
+            (Type::Char, Val::Char('☃')),
+            (Type::String, Val::String("héllo".into())),
+        ];
+        for (ty, val) in cases {
+            let bytes = val_to_scale(&val).unwrap();
+            assert_eq!(scale_to_val(&ty, &bytes).unwrap(), val, "{val:?}");
+        }
+    }
+
+    // One `Decoder` reads several values in sequence, as when decoding a
+    // function's concatenated argument list.
+    #[test]
+    fn decoder_reads_a_sequence() {
+        let vals = [Val::U32(7), Val::Bool(true), Val::String("hi".into())];
+        let mut bytes = Vec::new();
+        for val in &vals {
+            bytes.extend(val_to_scale(val).unwrap());
+        }
+
+        let types = [Type::U32, Type::Bool, Type::String];
+        let mut decoder = Decoder::new(&bytes);
+        for (ty, want) in types.iter().zip(&vals) {
+            assert_eq!(&decoder.decode(ty).unwrap(), want);
+        }
+        decoder.finish().unwrap();
+    }
+}
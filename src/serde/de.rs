@@ -45,6 +45,114 @@ impl TypeWrapper {
         v.try_into()
             .map_err(|_| DeError::invalid_value(unexpected, self))
     }
+
+    /// Resolve a buffered `Content` into a `variant`, `union`, or `result`
+    /// `Val`. The canonical tagged map form is tried first; failing that, each
+    /// candidate case is attempted untagged in declaration order and the first
+    /// that deserializes cleanly wins.
+    fn from_content<E: DeError>(self, content: Content) -> Result<Val, E> {
+        match self.0 {
+            Type::Variant(variant) => {
+                if let Some((key, value)) = tagged_entry(&content) {
+                    if let Some(case) = variant.cases().find(|case| case.name == key) {
+                        let payload = match case.ty {
+                            Some(ty) => Some(TypeWrapper(ty).deserialize(ContentDeserializer::new(
+                                value.clone(),
+                            ))?),
+                            None => None,
+                        };
+                        return variant.new_val(case.name, payload).map_err(DeError::custom);
+                    }
+                }
+                for case in variant.cases() {
+                    if let Some(payload) = try_untagged(&case.ty, &content) {
+                        return variant.new_val(case.name, payload).map_err(DeError::custom);
+                    }
+                }
+                Err(DeError::custom("no variant case matched the input"))
+            }
+
+            Type::Union(union) => {
+                if let Some((key, value)) = tagged_entry(&content) {
+                    if let Ok(idx) = key.parse::<u32>() {
+                        if let Some(ty) = union.types().nth(idx as usize) {
+                            let val = TypeWrapper(ty).deserialize(ContentDeserializer::new(
+                                value.clone(),
+                            ))?;
+                            return union.new_val(idx, val).map_err(DeError::custom);
+                        }
+                    }
+                }
+                for (idx, ty) in union.types().enumerate() {
+                    if let Ok(val) = TypeWrapper(ty)
+                        .deserialize(ContentDeserializer::<TrialError>::new(content.clone()))
+                    {
+                        return union.new_val(idx as u32, val).map_err(DeError::custom);
+                    }
+                }
+                Err(DeError::custom("no union case matched the input"))
+            }
+
+            Type::Result(result) => {
+                if let Some((key, value)) = tagged_entry(&content) {
+                    let val = match key {
+                        "result" => Some(Ok(opt_from_content(result.ok(), value)?)),
+                        "error" => Some(Err(opt_from_content(result.err(), value)?)),
+                        _ => None,
+                    };
+                    if let Some(val) = val {
+                        return result.new_val(val).map_err(DeError::custom);
+                    }
+                }
+                if let Some(payload) = try_untagged(&result.ok(), &content) {
+                    return result.new_val(Ok(payload)).map_err(DeError::custom);
+                }
+                if let Some(payload) = try_untagged(&result.err(), &content) {
+                    return result.new_val(Err(payload)).map_err(DeError::custom);
+                }
+                Err(DeError::custom("no result case matched the input"))
+            }
+
+            _ => unreachable!("from_content is only used for variant/union/result"),
+        }
+    }
+}
+
+/// View a buffered value as the canonical single-key tagged map `{key: value}`.
+fn tagged_entry(content: &Content) -> Option<(&str, &Content)> {
+    match content {
+        Content::Map(entries) if entries.len() == 1 => match &entries[0].0 {
+            Content::Str(key) => Some((key, &entries[0].1)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Attempt to deserialize a buffered value into an optional case payload,
+/// returning `None` if the case does not match. A `None` case type only matches
+/// a unit/null input.
+fn try_untagged(ty: &Option<Type>, content: &Content) -> Option<Option<Val>> {
+    match ty {
+        Some(ty) => TypeWrapper(ty.clone())
+            .deserialize(ContentDeserializer::<TrialError>::new(content.clone()))
+            .ok()
+            .map(Some),
+        None => matches!(content, Content::Unit | Content::None).then_some(None),
+    }
+}
+
+/// Throwaway error type for untagged case attempts, whose failures are
+/// discarded until every candidate has been tried.
+type TrialError = serde::de::value::Error;
+
+fn opt_from_content<E: DeError>(ty: Option<Type>, content: &Content) -> Result<Option<Val>, E> {
+    match ty {
+        Some(ty) => Ok(Some(
+            TypeWrapper(ty).deserialize(ContentDeserializer::new(content.clone()))?,
+        )),
+        None => Ok(None),
+    }
 }
 
 impl<'de> DeserializeSeed<'de> for TypeWrapper {
@@ -74,11 +182,15 @@ impl<'de> DeserializeSeed<'de> for TypeWrapper {
                 let len = tuple.types().len();
                 deserializer.deserialize_tuple(len, self)
             }
-            Type::Variant(_) => deserializer.deserialize_map(self),
+            // `variant`/`union`/`result` accept either the canonical tagged
+            // map (`{"case": value}`) or a bare untagged value. Buffer the input
+            // once and resolve it against the candidate cases in `from_content`.
+            Type::Variant(_) | Type::Union(_) | Type::Result(_) => {
+                let content = deserializer.deserialize_any(ContentVisitor)?;
+                self.from_content(content)
+            }
             Type::Enum(_) => deserializer.deserialize_str(self),
-            Type::Union(_) => deserializer.deserialize_map(self),
             Type::Option(_) => deserializer.deserialize_option(self),
-            Type::Result(_) => deserializer.deserialize_enum("result", &["result", "error"], self),
             Type::Flags(_) => deserializer.deserialize_seq(self),
         }
     }
@@ -218,6 +330,17 @@ impl<'de> Visitor<'de> for TypeWrapper {
                 Val::Char(c)
             }
             Type::String => Val::String(v.to_string().into_boxed_str()),
+            // Decimal-string inputs losslessly cover the full width of each
+            // integer type, unlike JSON numbers which most consumers truncate
+            // through f64. This is what makes `"18446744073709551615"` usable.
+            Type::U8 => Val::U8(parse_int_str(v, &self)?),
+            Type::U16 => Val::U16(parse_int_str(v, &self)?),
+            Type::U32 => Val::U32(parse_int_str(v, &self)?),
+            Type::U64 => Val::U64(parse_int_str(v, &self)?),
+            Type::S8 => Val::S8(parse_int_str(v, &self)?),
+            Type::S16 => Val::S16(parse_int_str(v, &self)?),
+            Type::S32 => Val::S32(parse_int_str(v, &self)?),
+            Type::S64 => Val::S64(parse_int_str(v, &self)?),
             Type::Enum(enum_) => enum_.new_val(v).map_err(DeError::custom)?,
             Type::List(list) if list.ty() == Type::U8 => {
                 let bytes = base64::decode(v).map_err(DeError::custom)?;
@@ -361,86 +484,272 @@ impl<'de> Visitor<'de> for TypeWrapper {
                     field_values.insert(idx, (key, val));
                 }
 
+                // Mirror serde's `missing_field`: `option<T>` fields default to
+                // `None` when omitted, any other missing field is an error.
+                for (name, (idx, ty)) in field_types {
+                    match ty {
+                        Type::Option(option) => {
+                            let val = option.new_val(None).map_err(DeError::custom)?;
+                            field_values.insert(idx, (name, val));
+                        }
+                        _ => return Err(DeError::custom(format!("missing field {name:?}"))),
+                    }
+                }
+
                 record
                     .new_val(field_values.into_values())
                     .map_err(DeError::custom)?
             }
 
-            Type::Variant(variant) => {
-                let key = map
-                    .next_key::<&str>()?
-                    .ok_or_else(|| DeError::custom("empty map for variant"))?;
+            // `variant`/`union`/`result` normally arrive through `deserialize`,
+            // which buffers the input through `ContentVisitor` and resolves it
+            // in `from_content`. When this visitor is driven directly (as by
+            // `deserialize_val`, or for an `option` payload in `visit_some`),
+            // the map is buffered here and resolved the same way.
+            Type::Variant(_) | Type::Union(_) | Type::Result(_) => {
+                let content = ContentVisitor.visit_map(map)?;
+                return self.clone().from_content(content);
+            }
+            _ => return Err(DeError::invalid_type(Unexpected::Map, &self)),
+        })
+    }
+}
 
-                let case = variant
-                    .cases()
-                    .find(|case| case.name == key)
-                    .ok_or_else(|| DeError::custom(format!("unknown case {key:?} for variant")))?;
+fn parse_int_str<T, E>(v: &str, exp: &dyn serde::de::Expected) -> Result<T, E>
+where
+    T: std::str::FromStr,
+    E: DeError,
+{
+    v.parse()
+        .map_err(|_| DeError::invalid_value(Unexpected::Str(v), exp))
+}
 
-                let val = next_optional_value(&mut map, case.ty)?;
+/// An owned buffer mirroring the serde data model, used to replay an input
+/// value across several candidate case types during untagged resolution.
+#[derive(Clone)]
+enum Content {
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+    Seq(Vec<Content>),
+    Map(Vec<(Content, Content)>),
+    None,
+    Some(Box<Content>),
+    Unit,
+}
 
-                if map.next_key::<IgnoredAny>()?.is_some() {
-                    return Err(DeError::custom("too many elements; expected one"));
-                }
+/// Captures any incoming value into an owned [`Content`].
+struct ContentVisitor;
 
-                variant.new_val(key, val).map_err(DeError::custom)?
-            }
+impl<'de> DeserializeSeed<'de> for ContentVisitor {
+    type Value = Content;
 
-            Type::Union(union) => {
-                let discriminant: u32 = map
-                    .next_key::<&str>()?
-                    .ok_or_else(|| DeError::custom("empty map for union"))?
-                    .parse()
-                    .map_err(|_| DeError::custom("invalid key for union"))?;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Content, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
 
-                let ty = union.types().nth(discriminant as usize).ok_or_else(|| {
-                    DeError::custom(format!("unknown case {discriminant} for union"))
-                })?;
+impl<'de> Visitor<'de> for ContentVisitor {
+    type Value = Content;
 
-                let val = map.next_value_seed(Self(ty))?;
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("any value")
+    }
 
-                if map.next_key::<IgnoredAny>()?.is_some() {
-                    return Err(DeError::custom("too many elements; expected one"));
-                }
+    fn visit_bool<E: DeError>(self, v: bool) -> Result<Content, E> {
+        Ok(Content::Bool(v))
+    }
 
-                union.new_val(discriminant, val).map_err(DeError::custom)?
-            }
+    fn visit_i64<E: DeError>(self, v: i64) -> Result<Content, E> {
+        Ok(Content::I64(v))
+    }
 
-            Type::Result(result) => {
-                let key = map
-                    .next_key()?
-                    .ok_or_else(|| DeError::custom("empty map for result"))?;
-
-                let val = match key {
-                    "result" => Ok(next_optional_value(&mut map, result.ok())?),
-                    "error" => Err(next_optional_value(&mut map, result.err())?),
-                    other => {
-                        return Err(DeError::custom(format!("unknown key {other:?} for result")))
-                    }
-                };
+    fn visit_u64<E: DeError>(self, v: u64) -> Result<Content, E> {
+        Ok(Content::U64(v))
+    }
 
-                if map.next_key::<IgnoredAny>()?.is_some() {
-                    return Err(DeError::custom("too many elements; expected one"));
-                }
+    fn visit_f64<E: DeError>(self, v: f64) -> Result<Content, E> {
+        Ok(Content::F64(v))
+    }
 
-                result.new_val(val).map_err(DeError::custom)?
-            }
+    fn visit_str<E: DeError>(self, v: &str) -> Result<Content, E> {
+        Ok(Content::Str(v.to_string()))
+    }
 
-            _ => return Err(DeError::invalid_type(Unexpected::Map, &self)),
-        })
+    fn visit_string<E: DeError>(self, v: String) -> Result<Content, E> {
+        Ok(Content::Str(v))
+    }
+
+    fn visit_bytes<E: DeError>(self, v: &[u8]) -> Result<Content, E> {
+        Ok(Content::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: DeError>(self, v: Vec<u8>) -> Result<Content, E> {
+        Ok(Content::Bytes(v))
+    }
+
+    fn visit_none<E: DeError>(self) -> Result<Content, E> {
+        Ok(Content::None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Content, D::Error> {
+        Ok(Content::Some(Box::new(deserializer.deserialize_any(self)?)))
+    }
+
+    fn visit_unit<E: DeError>(self) -> Result<Content, E> {
+        Ok(Content::Unit)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Content, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Content, A::Error> {
+        let mut vec = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(elem) = seq.next_element_seed(ContentVisitor)? {
+            vec.push(elem);
+        }
+        Ok(Content::Seq(vec))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Content, A::Error> {
+        let mut vec = Vec::with_capacity(map.size_hint().unwrap_or_default());
+        while let Some(key) = map.next_key_seed(ContentVisitor)? {
+            let value = map.next_value_seed(ContentVisitor)?;
+            vec.push((key, value));
+        }
+        Ok(Content::Map(vec))
+    }
+}
+
+/// Replays a buffered [`Content`] into a [`Deserializer`] so a case type can be
+/// attempted without re-reading the original input.
+struct ContentDeserializer<E> {
+    content: Content,
+    marker: std::marker::PhantomData<E>,
+}
+
+impl<E> ContentDeserializer<E> {
+    fn new(content: Content) -> Self {
+        Self {
+            content,
+            marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'de, E: DeError> Deserializer<'de> for ContentDeserializer<E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.content {
+            Content::Bool(v) => visitor.visit_bool(v),
+            Content::U64(v) => visitor.visit_u64(v),
+            Content::I64(v) => visitor.visit_i64(v),
+            Content::F64(v) => visitor.visit_f64(v),
+            Content::Str(v) => visitor.visit_string(v),
+            Content::Bytes(v) => visitor.visit_byte_buf(v),
+            Content::None => visitor.visit_none(),
+            Content::Some(v) => visitor.visit_some(ContentDeserializer::new(*v)),
+            Content::Unit => visitor.visit_unit(),
+            Content::Seq(v) => visitor.visit_seq(ContentSeqAccess::new(v)),
+            Content::Map(v) => visitor.visit_map(ContentMapAccess::new(v)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.content {
+            Content::None | Content::Unit => visitor.visit_none(),
+            Content::Some(v) => visitor.visit_some(ContentDeserializer::new(*v)),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf unit unit_struct newtype_struct seq tuple tuple_struct map
+        struct enum identifier ignored_any
+    }
+}
+
+struct ContentSeqAccess<E> {
+    iter: std::vec::IntoIter<Content>,
+    marker: std::marker::PhantomData<E>,
+}
+
+impl<E> ContentSeqAccess<E> {
+    fn new(vec: Vec<Content>) -> Self {
+        Self {
+            iter: vec.into_iter(),
+            marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'de, E: DeError> serde::de::SeqAccess<'de> for ContentSeqAccess<E> {
+    type Error = E;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, E> {
+        match self.iter.next() {
+            Some(content) => seed.deserialize(ContentDeserializer::new(content)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
     }
 }
 
-fn next_optional_value<'de, A: MapAccess<'de>>(
-    map: &mut A,
-    ty: Option<Type>,
-) -> Result<Option<Val>, A::Error> {
-    Ok(match ty {
-        Some(ty) => Some(map.next_value_seed(TypeWrapper(ty))?),
-        None => {
-            map.next_value::<()>()?;
-            None
+struct ContentMapAccess<E> {
+    iter: std::vec::IntoIter<(Content, Content)>,
+    value: Option<Content>,
+    marker: std::marker::PhantomData<E>,
+}
+
+impl<E> ContentMapAccess<E> {
+    fn new(vec: Vec<(Content, Content)>) -> Self {
+        Self {
+            iter: vec.into_iter(),
+            value: None,
+            marker: std::marker::PhantomData,
         }
-    })
+    }
+}
+
+impl<'de, E: DeError> MapAccess<'de> for ContentMapAccess<E> {
+    type Error = E;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, E> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(ContentDeserializer::new(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, E> {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(ContentDeserializer::new(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
 }
 
 fn expect_exactly_one<T, E: DeError>(
@@ -455,3 +764,51 @@ fn expect_exactly_one<T, E: DeError>(
         (Some(_), Some(_)) => Err(DeError::custom(too_many_msg)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::{de::DeserializeSeed, Deserialize};
+    use serde_json::{json, Value as JsonValue};
+
+    use super::{tagged_entry, Content, ContentDeserializer, ContentVisitor, TrialError};
+
+    fn buffer(value: JsonValue) -> Content {
+        ContentVisitor.deserialize(value).unwrap()
+    }
+
+    fn replay<'de, T: Deserialize<'de>>(content: &Content) -> Result<T, TrialError> {
+        T::deserialize(ContentDeserializer::new(content.clone()))
+    }
+
+    // Buffering and replaying must reproduce the input exactly, so untagged
+    // resolution sees the same value every case type is tried against.
+    #[test]
+    fn replays_nested_value() {
+        let value = json!({ "a": [1, 2, 3], "b": { "c": "x" }, "d": null });
+        let content = buffer(value.clone());
+        let round_trip: JsonValue = replay(&content).unwrap();
+        assert_eq!(round_trip, value);
+    }
+
+    // A single buffered value can be attempted as several shapes in turn; a
+    // failed attempt must not prevent the next one from succeeding.
+    #[test]
+    fn replays_same_content_as_different_shapes() {
+        let content = buffer(json!(42));
+        assert!(replay::<String>(&content).is_err());
+        assert_eq!(replay::<u64>(&content).unwrap(), 42);
+    }
+
+    // `from_content` only takes the tagged branch for a single-string-key map;
+    // anything else falls through to untagged first-match resolution.
+    #[test]
+    fn tagged_entry_only_matches_single_string_key() {
+        assert_eq!(
+            tagged_entry(&buffer(json!({ "ok": 1 }))).map(|(key, _)| key),
+            Some("ok"),
+        );
+        assert!(tagged_entry(&buffer(json!({ "a": 1, "b": 2 }))).is_none());
+        assert!(tagged_entry(&buffer(json!([1]))).is_none());
+        assert!(tagged_entry(&buffer(json!("scalar"))).is_none());
+    }
+}
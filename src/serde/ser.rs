@@ -71,6 +71,11 @@ impl Serialize for ValWrapper {
             }
 
             Val::Record(record) => {
+                // Fields are emitted in `record.fields()` (WIT declaration)
+                // order. Because a streaming serializer writes entries as they
+                // are fed, this is the one place that preserves WIT order
+                // without serde_json's `preserve_order` feature; it is what
+                // `crate::to_json_string` and the `/call` response build on.
                 let mut map = serializer.serialize_map(Some(record.ty().fields().len()))?;
                 for (key, val) in record.fields() {
                     map.serialize_entry(key, Self::wrap_ref(val))?;
@@ -27,3 +27,20 @@ fn simple_types_to_json() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn to_json_string_streams_scalars() -> Result<()> {
+    assert_eq!(crate::to_json_string(&Val::U32(12345678))?, "12345678");
+    assert_eq!(crate::to_json_string(&Val::String("ab".into()))?, "\"ab\"");
+    Ok(())
+}
+
+#[test]
+fn to_value_from_value_round_trips() -> Result<()> {
+    use wasmtime::component::Type;
+
+    let (json, attachments) = crate::to_value(&Val::U32(5));
+    assert!(attachments.is_empty());
+    assert_eq!(crate::from_value(&Type::U32, json, &attachments)?, Val::U32(5));
+    Ok(())
+}